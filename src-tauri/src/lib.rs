@@ -1,21 +1,124 @@
 //! StarScope Tauri 應用程式核心邏輯，包含 sidecar 管理、系統匣與視窗控制。
 
-use std::sync::Mutex;
+use std::borrow::Cow;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
 use tauri::{
-    menu::{Menu, MenuItem},
-    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    App, AppHandle, Emitter, Manager, WindowEvent,
+    http::{header, Request, Response, StatusCode},
+    menu::{IsMenuItem, Menu, MenuItem, PredefinedMenuItem},
+    tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent},
+    App, AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize, WindowEvent,
+};
+use tauri_plugin_process::ProcessExt;
+use tauri_plugin_shell::{
+    process::{CommandChild, CommandEvent},
+    ShellExt,
 };
-use tauri_plugin_shell::{process::CommandChild, ShellExt};
+use tauri_plugin_updater::UpdaterExt;
+use tokio::sync::mpsc::Receiver;
 use tracing::{info, warn};
 
-/// 保存 sidecar 子程序以便退出時清理。
+/// 兩次自動檢查更新之間的間隔。
+const UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// 連續重啟失敗次數上限，超過後放棄並標記為 `Failed`。
+const MAX_RESTART_ATTEMPTS: u32 = 5;
+/// 重啟退避的起始延遲。
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+/// 重啟退避的延遲上限。
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// 子程序需保持存活多久才視為「穩定」並重置重啟計數。
+const STABLE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Sidecar 目前的狀態，會同步到 `sidecar-status` 事件與 `sidecar_status` 指令。
+#[derive(Clone, Copy, Serialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+enum SidecarStatus {
+    Running,
+    Restarting { attempt: u32 },
+    Failed,
+}
+
+/// 保存 sidecar 子程序與監控狀態，以便退出時清理、失敗時自動重啟。
 struct SidecarState {
     child: Mutex<Option<CommandChild>>,
+    status: Mutex<SidecarStatus>,
+    /// 監控任務的關閉旗標，由 `cleanup_sidecar` 設定以避免關閉時又被重啟。
+    shutdown: Arc<AtomicBool>,
+}
+
+/// 系統匣選單上顯示的單一追蹤中儲存庫，由前端在清單或未讀數變動時同步過來。
+#[derive(Clone, Deserialize)]
+struct TrayRepo {
+    id: String,
+    name: String,
+    /// 該儲存庫的未讀活動數量，大於 0 時會顯示在選單項目與提示文字上。
+    unread: u32,
+}
+
+/// 保存系統匣圖示控制代碼與目前選單內容，以便在 `update_tray`／有新版本時於執行期重建選單。
+struct TrayState {
+    tray: TrayIcon,
+    repos: Mutex<Vec<TrayRepo>>,
+    update_available: Mutex<bool>,
+}
+
+/// 提供給前端的更新資訊摘要。
+#[derive(Clone, Serialize)]
+struct UpdateInfo {
+    version: String,
+    notes: Option<String>,
+}
+
+/// 保存目前偵測到、尚待安裝的更新。
+struct UpdaterState {
+    pending: Mutex<Option<tauri_plugin_updater::Update>>,
+}
+
+/// 持久化於磁碟上的主視窗幾何資訊。
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct WindowGeometry {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    maximized: bool,
+    fullscreen: bool,
+}
+
+/// 使用者偏好設定，目前僅有「關閉時縮到系統匣」。
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct WindowPreferences {
+    #[serde(default)]
+    close_to_tray: bool,
+}
+
+/// 保存目前載入的使用者偏好設定，供 `on_window_event` 判斷關閉行為。
+struct PreferencesState {
+    close_to_tray: Mutex<bool>,
+}
+
+/// 視窗幾何儲存的 debounce 延遲：避免拖曳/縮放視窗時每個事件都觸發磁碟寫入。
+const WINDOW_SAVE_DEBOUNCE: Duration = Duration::from_millis(400);
+
+/// 視窗幾何 debounce 用的世代計數器；每次移動/縮放遞增，延遲寫入前比對世代以捨棄過期任務。
+struct WindowSaveState {
+    generation: Arc<AtomicU64>,
 }
 
 #[cfg(target_os = "macos")]
-use objc2_app_kit::{NSWindow, NSWindowButton, NSWindowCollectionBehavior};
+use objc2_app_kit::{
+    NSWindow, NSWindowButton, NSWindowCollectionBehavior, NSWindowStyleMask,
+    NSWindowTitleVisibility,
+};
+
+/// 預設的紅綠燈按鈕內縮距離（像素），對應自訂標題列的留白。
+#[cfg(target_os = "macos")]
+const DEFAULT_TITLEBAR_INSET: (f64, f64) = (20.0, 20.0);
 
 /// 停用 macOS 原生全螢幕按鈕，繞過 macOS 26 beta 全螢幕切換時的當機問題。
 /// See: https://github.com/tauri-apps/tauri/issues/11336
@@ -50,36 +153,555 @@ fn disable_fullscreen_button(window: &tauri::WebviewWindow) {
     }
 }
 
-/// 啟動 Python sidecar，失敗時優雅降級。
-fn start_sidecar(app: &App) {
-    let state = match app.shell().sidecar("starscope-sidecar") {
-        Ok(cmd) => match cmd.spawn() {
-            Ok((_rx, child)) => SidecarState {
-                child: Mutex::new(Some(child)),
-            },
-            Err(e) => {
-                warn!("sidecar 啟動失敗: {e}，開發環境請執行 './start-dev.sh'");
-                SidecarState {
-                    child: Mutex::new(None),
+/// 將視窗設為隱藏原生標題列、由網頁 UI 繪製標題列的外觀，並把紅綠燈按鈕內縮到指定位置。
+/// 網頁端需在自訂標題列區域加上 `data-tauri-drag-region` 屬性才能拖曳視窗。
+#[cfg(target_os = "macos")]
+fn setup_custom_titlebar(window: &tauri::WebviewWindow) {
+    let (x, y) = DEFAULT_TITLEBAR_INSET;
+    if let Err(e) = window.with_webview(move |webview| {
+        // SAFETY: 同 disable_fullscreen_button，ns_window_ptr 僅在 callback 範圍內使用且已檢查非空。
+        unsafe {
+            let ns_window_ptr = webview.ns_window();
+            if ns_window_ptr.is_null() {
+                warn!("Failed to get NSWindow pointer - pointer is null");
+                return;
+            }
+            let ns_window: &NSWindow = &*(ns_window_ptr as *const NSWindow);
+
+            ns_window.setTitlebarAppearsTransparent(true);
+            ns_window.setTitleVisibility(NSWindowTitleVisibility::Hidden);
+            let style_mask = ns_window.styleMask() | NSWindowStyleMask::FullSizeContentView;
+            ns_window.setStyleMask(style_mask);
+
+            reposition_traffic_lights(ns_window, x, y);
+        }
+    }) {
+        warn!("Failed to set up custom titlebar: {e}");
+    }
+}
+
+/// 將原生關閉/縮小/縮放按鈕從左上角內縮 `(x, y)` 像素（以關閉按鈕為準，保留三個按鈕之間原有的間距）。
+#[cfg(target_os = "macos")]
+unsafe fn reposition_traffic_lights(ns_window: &NSWindow, x: f64, y: f64) {
+    let Some(close_button) = ns_window.standardWindowButton(NSWindowButton::CloseButton) else {
+        return;
+    };
+    let close_origin_x = close_button.frame().origin.x;
+    for button_kind in [
+        NSWindowButton::CloseButton,
+        NSWindowButton::MiniaturizeButton,
+        NSWindowButton::ZoomButton,
+    ] {
+        let Some(button) = ns_window.standardWindowButton(button_kind) else {
+            continue;
+        };
+        let Some(titlebar_view) = button.superview() else {
+            continue;
+        };
+        let mut frame = button.frame();
+        frame.origin.x = x + (frame.origin.x - close_origin_x);
+        frame.origin.y = titlebar_view.frame().size.height - frame.size.height - y;
+        button.setFrame(frame);
+    }
+}
+
+/// 設定紅綠燈按鈕的內縮位置；非 macOS 平台為 no-op，改由前端繪製視窗控制按鈕並呼叫
+/// `window.minimize()`/`window.close()`。
+#[tauri::command]
+fn set_titlebar_buttons_inset(window: tauri::WebviewWindow, x: f64, y: f64) {
+    #[cfg(target_os = "macos")]
+    {
+        if let Err(e) = window.with_webview(move |webview| unsafe {
+            let ns_window_ptr = webview.ns_window();
+            if ns_window_ptr.is_null() {
+                warn!("Failed to get NSWindow pointer - pointer is null");
+                return;
+            }
+            let ns_window: &NSWindow = &*(ns_window_ptr as *const NSWindow);
+            reposition_traffic_lights(ns_window, x, y);
+        }) {
+            warn!("Failed to reposition titlebar buttons: {e}");
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (window, x, y);
+    }
+}
+
+/// 主視窗幾何資訊的存檔路徑：`<TAURI_APP_DATA_DIR>/window-state.json`。
+fn window_state_path(app: &AppHandle) -> Option<std::path::PathBuf> {
+    app.path().app_data_dir().ok().map(|dir| dir.join("window-state.json"))
+}
+
+/// 偏好設定的存檔路徑：`<TAURI_APP_DATA_DIR>/preferences.json`。
+fn preferences_path(app: &AppHandle) -> Option<std::path::PathBuf> {
+    app.path().app_data_dir().ok().map(|dir| dir.join("preferences.json"))
+}
+
+/// 將主視窗目前的大小、位置與最大化/全螢幕狀態寫入磁碟，供下次啟動還原。
+fn save_window_geometry(window: &tauri::WebviewWindow) {
+    let Some(path) = window_state_path(&window.app_handle()) else {
+        return;
+    };
+
+    let Ok(position) = window.outer_position() else {
+        return;
+    };
+    let Ok(size) = window.outer_size() else {
+        return;
+    };
+    let maximized = window.is_maximized().unwrap_or(false);
+    let fullscreen = window.is_fullscreen().unwrap_or(false);
+
+    let geometry = WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        maximized,
+        fullscreen,
+    };
+
+    if let Some(parent) = path.parent() {
+        if let Err(e) = std::fs::create_dir_all(parent) {
+            warn!("Failed to create app data dir for window state: {e}");
+            return;
+        }
+    }
+    match serde_json::to_vec(&geometry) {
+        Ok(bytes) => {
+            if let Err(e) = std::fs::write(&path, bytes) {
+                warn!("Failed to save window geometry: {e}");
+            }
+        }
+        Err(e) => warn!("Failed to serialize window geometry: {e}"),
+    }
+}
+
+/// 以 debounce 方式排程視窗幾何儲存：每次呼叫都會讓先前排定但尚未執行的寫入作廢，
+/// 並在閒置 `WINDOW_SAVE_DEBOUNCE` 之後才真正寫檔，避免拖曳/縮放視窗時灌爆磁碟 I/O。
+fn schedule_save_window_geometry(app: &AppHandle) {
+    let Some(state) = app.try_state::<WindowSaveState>() else {
+        return;
+    };
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let generation_counter = state.generation.clone();
+    let app = app.clone();
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(WINDOW_SAVE_DEBOUNCE).await;
+        if generation_counter.load(Ordering::SeqCst) != generation {
+            // 在等待期間又發生了新的移動/縮放事件，這次排程已經過期。
+            return;
+        }
+        if let Some(window) = app.get_webview_window("main") {
+            save_window_geometry(&window);
+        }
+    });
+}
+
+/// 檢查儲存的視窗矩形是否與目前至少一個可用螢幕有重疊，避免在螢幕佈局變動（例如拔掉外接螢幕）
+/// 後把視窗還原到畫面之外、使用者無法再拖回來。
+fn geometry_on_available_monitor(window: &tauri::WebviewWindow, geometry: &WindowGeometry) -> bool {
+    let Ok(monitors) = window.available_monitors() else {
+        return false;
+    };
+
+    let window_left = geometry.x;
+    let window_top = geometry.y;
+    let window_right = window_left + geometry.width as i32;
+    let window_bottom = window_top + geometry.height as i32;
+
+    monitors.iter().any(|monitor| {
+        let monitor_left = monitor.position().x;
+        let monitor_top = monitor.position().y;
+        let monitor_right = monitor_left + monitor.size().width as i32;
+        let monitor_bottom = monitor_top + monitor.size().height as i32;
+
+        window_left < monitor_right
+            && window_right > monitor_left
+            && window_top < monitor_bottom
+            && window_bottom > monitor_top
+    })
+}
+
+/// 從磁碟還原主視窗的大小、位置與最大化/全螢幕狀態（若沒有存檔則保留預設值）。
+fn restore_window_geometry(window: &tauri::WebviewWindow) {
+    let Some(path) = window_state_path(&window.app_handle()) else {
+        return;
+    };
+    let Ok(bytes) = std::fs::read(&path) else {
+        return;
+    };
+    let Ok(geometry) = serde_json::from_slice::<WindowGeometry>(&bytes) else {
+        warn!("Failed to parse saved window geometry, ignoring");
+        return;
+    };
+
+    if geometry_on_available_monitor(window, &geometry) {
+        if let Err(e) = window.set_position(PhysicalPosition::new(geometry.x, geometry.y)) {
+            warn!("Failed to restore window position: {e}");
+        }
+        if let Err(e) = window.set_size(PhysicalSize::new(geometry.width, geometry.height)) {
+            warn!("Failed to restore window size: {e}");
+        }
+    } else {
+        warn!(
+            "Saved window geometry is off the currently available screens, keeping default placement and size"
+        );
+    }
+    if geometry.maximized {
+        if let Err(e) = window.maximize() {
+            warn!("Failed to restore maximized state: {e}");
+        }
+    }
+    if geometry.fullscreen {
+        if let Err(e) = window.set_fullscreen(true) {
+            warn!("Failed to restore fullscreen state: {e}");
+        }
+    }
+}
+
+/// 從磁碟載入使用者偏好設定；沒有存檔或解析失敗時回傳預設值（`close_to_tray = false`）。
+fn load_preferences(app: &AppHandle) -> WindowPreferences {
+    preferences_path(app)
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice::<WindowPreferences>(&bytes).ok())
+        .unwrap_or(WindowPreferences {
+            close_to_tray: false,
+        })
+}
+
+/// 查詢目前是否啟用「關閉時縮到系統匣」。
+fn is_close_to_tray_enabled(app: &AppHandle) -> bool {
+    app.try_state::<PreferencesState>()
+        .and_then(|state| state.close_to_tray.lock().ok().map(|guard| *guard))
+        .unwrap_or(false)
+}
+
+/// 設定「關閉時縮到系統匣」偏好並寫入磁碟。
+#[tauri::command]
+fn set_close_to_tray(app: AppHandle, enabled: bool) -> Result<(), String> {
+    if let Some(state) = app.try_state::<PreferencesState>() {
+        if let Ok(mut guard) = state.close_to_tray.lock() {
+            *guard = enabled;
+        }
+    }
+
+    let Some(path) = preferences_path(&app) else {
+        return Err("App data dir not available".to_string());
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let bytes = serde_json::to_vec(&WindowPreferences {
+        close_to_tray: enabled,
+    })
+    .map_err(|e| e.to_string())?;
+    std::fs::write(&path, bytes).map_err(|e| e.to_string())
+}
+
+/// 解析 `Range: bytes=start-end` 標頭（RFC 7233），回傳 `(start, end)`，`end` 為 `None` 代表到檔案結尾。
+fn parse_range_header(header_value: &str) -> Option<(u64, Option<u64>)> {
+    let spec = header_value.strip_prefix("bytes=")?;
+    let (start_s, end_s) = spec.split_once('-')?;
+    let start = start_s.parse::<u64>().ok()?;
+    let end = if end_s.is_empty() {
+        None
+    } else {
+        Some(end_s.parse::<u64>().ok()?)
+    };
+    Some((start, end))
+}
+
+/// 回傳一個只帶狀態碼、空內容的回應，用於錯誤情形。
+fn empty_response(status: StatusCode) -> Response<Cow<'static, [u8]>> {
+    Response::builder()
+        .status(status)
+        .body(Cow::Borrowed(&[][..]))
+        .unwrap_or_else(|_| Response::new(Cow::Borrowed(&[][..])))
+}
+
+/// `starscope://data/<path>` 協定處理器：讀取 sidecar 事先匯出到 app data 目錄下的大型
+/// JSON/CSV 檔案（sidecar 把結果寫到磁碟後由此處理器提供服務，並非即時代理 sidecar 程序），
+/// 並依 RFC 7233 支援 `Range` 請求，讓前端能漸進式載入、續傳大型結果集。
+fn handle_data_protocol(
+    app: &AppHandle,
+    request: &Request<Vec<u8>>,
+) -> Response<Cow<'static, [u8]>> {
+    let relative_path = request.uri().path().trim_start_matches('/');
+    if relative_path.is_empty() {
+        return empty_response(StatusCode::BAD_REQUEST);
+    }
+
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        return empty_response(StatusCode::INTERNAL_SERVER_ERROR);
+    };
+    let exports_dir = app_data_dir.join("exports");
+    let Ok(canonical_exports_dir) = std::fs::canonicalize(&exports_dir) else {
+        return empty_response(StatusCode::NOT_FOUND);
+    };
+    let file_path = exports_dir.join(relative_path);
+
+    // 先以 canonicalize 確認路徑實際存在且仍在 exports 目錄之下，再開檔，避免對任意檔案系統
+    // 路徑（例如絕對路徑跳脫 join、符號連結）留下「存在與否」的探測管道；兩種失敗情形一律回
+    // 404，不額外區分「不存在」與「跳脫沙箱」。
+    let Ok(canonical_file_path) = std::fs::canonicalize(&file_path) else {
+        return empty_response(StatusCode::NOT_FOUND);
+    };
+    if !canonical_file_path.starts_with(&canonical_exports_dir) {
+        return empty_response(StatusCode::NOT_FOUND);
+    }
+
+    let Ok(mut file) = std::fs::File::open(&canonical_file_path) else {
+        return empty_response(StatusCode::NOT_FOUND);
+    };
+    let Ok(total) = file.metadata().map(|m| m.len()) else {
+        return empty_response(StatusCode::INTERNAL_SERVER_ERROR);
+    };
+
+    let range_header = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok());
+    let requested_range = range_header.and_then(parse_range_header);
+
+    let (start, end) = match requested_range {
+        Some((start, end)) => (start, end.unwrap_or_else(|| total.saturating_sub(1))),
+        None => (0, total.saturating_sub(1)),
+    };
+
+    // 只有明確帶了 `Range` 標頭時才需要驗證範圍是否可滿足；沒帶 `Range` 的一般 GET 即使檔案
+    // 是 0 bytes 也該回 200 搭配空內容，而不是 416。
+    if requested_range.is_some() && (total == 0 || start > end || end >= total) {
+        return Response::builder()
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{total}"))
+            .body(Cow::Borrowed(&[][..]))
+            .unwrap_or_else(|_| Response::new(Cow::Borrowed(&[][..])));
+    }
+
+    let length = if total == 0 { 0 } else { (end - start + 1) as usize };
+    if file.seek(SeekFrom::Start(start)).is_err() {
+        return empty_response(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    let mut buf = vec![0u8; length];
+    if file.read_exact(&mut buf).is_err() {
+        return empty_response(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let status = if requested_range.is_some() {
+        StatusCode::PARTIAL_CONTENT
+    } else {
+        StatusCode::OK
+    };
+
+    let mut builder = Response::builder()
+        .status(status)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CONTENT_LENGTH, buf.len().to_string());
+    if requested_range.is_some() {
+        builder = builder.header(header::CONTENT_RANGE, format!("bytes {start}-{end}/{total}"));
+    }
+
+    builder
+        .body(Cow::Owned(buf))
+        .unwrap_or_else(|_| Response::new(Cow::Borrowed(&[][..])))
+}
+
+/// 嘗試產生 sidecar 子程序，回傳事件接收端與子程序控制代碼。
+fn spawn_sidecar(app: &AppHandle) -> Result<(Receiver<CommandEvent>, CommandChild), String> {
+    let cmd = app
+        .shell()
+        .sidecar("starscope-sidecar")
+        .map_err(|e| format!("找不到 sidecar: {e}，開發環境請執行 './start-dev.sh'"))?;
+    cmd.spawn()
+        .map_err(|e| format!("sidecar 啟動失敗: {e}，開發環境請執行 './start-dev.sh'"))
+}
+
+/// 更新 sidecar 狀態並廣播 `sidecar-status` 事件給主視窗。
+fn set_sidecar_status(app: &AppHandle, status: SidecarStatus) {
+    if let Some(state) = app.try_state::<SidecarState>() {
+        if let Ok(mut guard) = state.status.lock() {
+            *guard = status;
+        }
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.emit("sidecar-status", status) {
+            warn!("Failed to emit sidecar-status event: {e}");
+        }
+    }
+}
+
+/// 監控 sidecar 事件通道：偵測終止/錯誤並以指數退避自動重啟，直到連續失敗次數超過上限。
+async fn monitor_sidecar(app: AppHandle, mut rx: Receiver<CommandEvent>, shutdown: Arc<AtomicBool>) {
+    let mut attempt: u32 = 0;
+
+    loop {
+        let stable_timer = tokio::time::sleep(STABLE_WINDOW);
+        tokio::pin!(stable_timer);
+
+        let event = loop {
+            if shutdown.load(Ordering::Relaxed) {
+                info!("Sidecar monitor 收到關閉訊號，停止監控");
+                return;
+            }
+            tokio::select! {
+                event = rx.recv() => break event,
+                _ = &mut stable_timer => {
+                    if attempt > 0 {
+                        attempt = 0;
+                        info!("Sidecar 已穩定運行 {}s，重置重啟計數", STABLE_WINDOW.as_secs());
+                    }
+                    stable_timer.as_mut().reset(tokio::time::Instant::now() + STABLE_WINDOW);
                 }
             }
-        },
-        Err(e) => {
-            warn!("找不到 sidecar: {e}，開發環境請執行 './start-dev.sh'");
-            SidecarState {
-                child: Mutex::new(None),
+        };
+
+        match event {
+            Some(CommandEvent::Terminated(payload)) => {
+                warn!("Sidecar 已終止: {:?}", payload);
             }
+            Some(CommandEvent::Error(e)) => {
+                warn!("Sidecar 發生錯誤: {e}");
+            }
+            Some(_) => continue,
+            None => {
+                warn!("Sidecar 事件通道已關閉");
+            }
+        }
+
+        if shutdown.load(Ordering::Relaxed) {
+            info!("Sidecar monitor 收到關閉訊號，停止監控");
+            return;
+        }
+
+        attempt += 1;
+        if attempt > MAX_RESTART_ATTEMPTS {
+            set_sidecar_status(&app, SidecarStatus::Failed);
+            warn!("Sidecar 重啟已達上限（{MAX_RESTART_ATTEMPTS} 次），放棄自動重啟");
+            return;
+        }
+
+        set_sidecar_status(&app, SidecarStatus::Restarting { attempt });
+
+        let backoff = (BASE_BACKOFF * 2u32.pow(attempt - 1)).min(MAX_BACKOFF);
+        tokio::time::sleep(backoff).await;
+
+        if shutdown.load(Ordering::Relaxed) {
+            info!("Sidecar monitor 收到關閉訊號，停止監控");
+            return;
+        }
+
+        match spawn_sidecar(&app) {
+            Ok((new_rx, new_child)) => {
+                if let Some(state) = app.try_state::<SidecarState>() {
+                    if let Ok(mut guard) = state.child.lock() {
+                        *guard = Some(new_child);
+                    }
+                }
+                rx = new_rx;
+                set_sidecar_status(&app, SidecarStatus::Running);
+                info!("Sidecar 重啟成功（第 {attempt} 次嘗試）");
+            }
+            Err(e) => {
+                warn!("Sidecar 重啟失敗: {e}");
+            }
+        }
+    }
+}
+
+/// 啟動 Python sidecar 並監督其存活狀態：執行中終止或出錯時自動以指數退避重啟；
+/// 初次啟動失敗則直接標記為 `Failed`，不會重試。
+fn start_sidecar(app: &App) {
+    let handle = app.handle().clone();
+    let shutdown = Arc::new(AtomicBool::new(false));
+
+    let (child, status, rx) = match spawn_sidecar(&handle) {
+        Ok((rx, child)) => (Some(child), SidecarStatus::Running, Some(rx)),
+        Err(e) => {
+            warn!("{e}");
+            (None, SidecarStatus::Failed, None)
         }
     };
-    app.manage(state);
+
+    app.manage(SidecarState {
+        child: Mutex::new(child),
+        status: Mutex::new(status),
+        shutdown: shutdown.clone(),
+    });
+    // 透過 set_sidecar_status 廣播初次啟動的狀態，讓前端不必在掛載時額外輪詢一次才能拿到初始值。
+    set_sidecar_status(&handle, status);
+
+    if let Some(rx) = rx {
+        tauri::async_runtime::spawn(monitor_sidecar(handle, rx, shutdown));
+    }
 }
 
-/// 設定系統匣圖示與選單。
-fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+/// 回傳目前 sidecar 狀態，供前端輪詢使用。
+#[tauri::command]
+fn sidecar_status(app: AppHandle) -> SidecarStatus {
+    app.try_state::<SidecarState>()
+        .and_then(|state| state.status.lock().ok().map(|guard| *guard))
+        .unwrap_or(SidecarStatus::Failed)
+}
+
+/// 依目前追蹤的儲存庫清單與更新狀態組出系統匣選單：若有可用更新則在最上方顯示醒目的
+/// 「Update StarScope」項目，接著是固定的 Show/Refresh 項目、一段最近/加星儲存庫，最後是 Quit。
+fn build_tray_menu(
+    app: &AppHandle,
+    repos: &[TrayRepo],
+    update_available: bool,
+) -> tauri::Result<Menu<tauri::Wry>> {
     let show_item = MenuItem::with_id(app, "show", "Show StarScope", true, None::<&str>)?;
     let refresh_item = MenuItem::with_id(app, "refresh", "Refresh All", true, None::<&str>)?;
     let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-    let menu = Menu::with_items(app, &[&show_item, &refresh_item, &quit_item])?;
+
+    let mut items: Vec<Box<dyn IsMenuItem<tauri::Wry>>> = Vec::new();
+
+    if update_available {
+        items.push(Box::new(MenuItem::with_id(
+            app,
+            "update",
+            "⬆️ Update StarScope",
+            true,
+            None::<&str>,
+        )?));
+        items.push(Box::new(PredefinedMenuItem::separator(app)?));
+    }
+
+    items.push(Box::new(show_item));
+    items.push(Box::new(refresh_item));
+
+    if !repos.is_empty() {
+        items.push(Box::new(PredefinedMenuItem::separator(app)?));
+        for repo in repos {
+            let label = if repo.unread > 0 {
+                format!("{} ({})", repo.name, repo.unread)
+            } else {
+                repo.name.clone()
+            };
+            items.push(Box::new(MenuItem::with_id(
+                app,
+                format!("repo:{}", repo.id),
+                label,
+                true,
+                None::<&str>,
+            )?));
+        }
+        items.push(Box::new(PredefinedMenuItem::separator(app)?));
+    }
+
+    items.push(Box::new(quit_item));
+
+    let refs: Vec<&dyn IsMenuItem<tauri::Wry>> = items.iter().map(|item| item.as_ref()).collect();
+    Menu::with_items(app, &refs)
+}
+
+/// 設定系統匣圖示與選單。
+fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    let menu = build_tray_menu(app.handle(), &[], false)?;
 
     // 安全取得預設視窗圖示，未設定時回傳錯誤
     let icon = app
@@ -87,20 +709,64 @@ fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
         .ok_or("No default window icon configured in tauri.conf.json")?
         .clone();
 
-    let _tray = TrayIconBuilder::new()
+    let tray = TrayIconBuilder::new()
         .icon(icon)
         .menu(&menu)
         .tooltip("StarScope - GitHub Project Intelligence")
+        .show_menu_on_left_click(false)
         .on_menu_event(handle_tray_menu_event)
         .on_tray_icon_event(handle_tray_click)
         .build(app)?;
 
+    app.manage(TrayState {
+        tray,
+        repos: Mutex::new(Vec::new()),
+        update_available: Mutex::new(false),
+    });
+
     Ok(())
 }
 
+/// 依 `TrayState` 目前保存的儲存庫清單與更新狀態重建選單與提示文字。
+fn refresh_tray(app: &AppHandle) -> Result<(), String> {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return Err("Tray icon not initialized".to_string());
+    };
+
+    let repos = state.repos.lock().map_err(|_| "tray state poisoned")?.clone();
+    let update_available = *state.update_available.lock().map_err(|_| "tray state poisoned")?;
+
+    let menu = build_tray_menu(app, &repos, update_available).map_err(|e| e.to_string())?;
+    state.tray.set_menu(Some(menu)).map_err(|e| e.to_string())?;
+
+    let total_unread: u32 = repos.iter().map(|repo| repo.unread).sum();
+    let tooltip = match (update_available, total_unread) {
+        (true, 0) => "StarScope - 有可用更新".to_string(),
+        (true, n) => format!("StarScope - 有可用更新・{n} 則未讀動態"),
+        (false, 0) => "StarScope - GitHub Project Intelligence".to_string(),
+        (false, n) => format!("StarScope - {n} 則未讀動態"),
+    };
+    state
+        .tray
+        .set_tooltip(Some(tooltip.as_str()))
+        .map_err(|e| e.to_string())
+}
+
+/// 依前端傳入的儲存庫清單重建系統匣選單，並以未讀活動總數更新提示文字。
+#[tauri::command]
+fn update_tray(app: AppHandle, items: Vec<TrayRepo>) -> Result<(), String> {
+    if let Some(state) = app.try_state::<TrayState>() {
+        if let Ok(mut guard) = state.repos.lock() {
+            *guard = items;
+        }
+    }
+    refresh_tray(&app)
+}
+
 /// 處理系統匣選單點擊事件。
 fn handle_tray_menu_event(app: &AppHandle, event: tauri::menu::MenuEvent) {
-    match event.id.as_ref() {
+    let id = event.id.as_ref();
+    match id {
         "show" => show_main_window(app),
         "refresh" => {
             if let Some(window) = app.get_webview_window("main") {
@@ -109,20 +775,49 @@ fn handle_tray_menu_event(app: &AppHandle, event: tauri::menu::MenuEvent) {
                 }
             }
         }
-        "quit" => app.exit(0),
-        _ => {}
+        "quit" => {
+            cleanup_sidecar(app);
+            app.exit(0);
+        }
+        "update" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = install_update_inner(&app).await {
+                    warn!("Failed to install update from tray: {e}");
+                }
+            });
+        }
+        _ => {
+            if let Some(repo_id) = id.strip_prefix("repo:") {
+                if let Some(window) = app.get_webview_window("main") {
+                    if let Err(e) = window.emit("open-repo", repo_id) {
+                        warn!("Failed to emit open-repo event: {e}");
+                    }
+                }
+            }
+        }
     }
 }
 
-/// 處理系統匣圖示點擊（左鍵顯示視窗）。
+/// 處理系統匣圖示點擊：左鍵顯示主視窗，右鍵彈出選單（因 `show_menu_on_left_click(false)`，
+/// 右鍵的原生選單彈出行為維持啟用）。
 fn handle_tray_click(tray: &tauri::tray::TrayIcon, event: TrayIconEvent) {
-    if let TrayIconEvent::Click {
-        button: MouseButton::Left,
-        button_state: MouseButtonState::Up,
-        ..
-    } = event
-    {
-        show_main_window(tray.app_handle());
+    match event {
+        TrayIconEvent::Click {
+            button: MouseButton::Left,
+            button_state: MouseButtonState::Up,
+            ..
+        } => {
+            show_main_window(tray.app_handle());
+        }
+        TrayIconEvent::Click {
+            button: MouseButton::Right,
+            button_state: MouseButtonState::Up,
+            ..
+        } => {
+            info!("Tray right-click: showing context menu");
+        }
+        _ => {}
     }
 }
 
@@ -138,9 +833,120 @@ fn show_main_window(app: &AppHandle) {
     }
 }
 
-/// 視窗關閉時清理 sidecar 程序。
+/// 向發布端點查詢是否有新版本；若有，記錄待安裝的更新、廣播 `update-available` 事件並在
+/// 系統匣選單加上醒目的更新項目。
+async fn check_for_updates_inner(app: &AppHandle) -> Result<Option<UpdateInfo>, String> {
+    let updater = app.updater().map_err(|e| e.to_string())?;
+    let update = updater.check().await.map_err(|e| e.to_string())?;
+
+    let info = update.as_ref().map(|u| UpdateInfo {
+        version: u.version.clone(),
+        notes: u.body.clone(),
+    });
+
+    if let Some(state) = app.try_state::<UpdaterState>() {
+        if let Ok(mut guard) = state.pending.lock() {
+            *guard = update;
+        }
+    }
+
+    if let Some(info) = &info {
+        if let Some(window) = app.get_webview_window("main") {
+            if let Err(e) = window.emit("update-available", info.clone()) {
+                warn!("Failed to emit update-available event: {e}");
+            }
+        }
+
+        let changed = if let Some(state) = app.try_state::<TrayState>() {
+            state
+                .update_available
+                .lock()
+                .ok()
+                .map(|mut guard| {
+                    let changed = !*guard;
+                    *guard = true;
+                    changed
+                })
+                .unwrap_or(false)
+        } else {
+            false
+        };
+        if changed {
+            if let Err(e) = refresh_tray(app) {
+                warn!("Failed to refresh tray after update check: {e}");
+            }
+        }
+
+        info!("發現新版本 {}", info.version);
+    } else {
+        // 先前曾標記有可用更新，但這次檢查已經沒有了（例如伺服器撤回該版本），清除系統匣上的
+        // 提示，避免「⬆️ Update StarScope」卡在選單上。
+        let changed = if let Some(state) = app.try_state::<TrayState>() {
+            state
+                .update_available
+                .lock()
+                .ok()
+                .map(|mut guard| {
+                    let changed = *guard;
+                    *guard = false;
+                    changed
+                })
+                .unwrap_or(false)
+        } else {
+            false
+        };
+        if changed {
+            if let Err(e) = refresh_tray(app) {
+                warn!("Failed to refresh tray after update check: {e}");
+            }
+        }
+    }
+
+    Ok(info)
+}
+
+/// 下載並安裝目前待處理的更新；安裝完成後、重新啟動應用程式之前先關閉 sidecar，避免重啟後變成孤兒程序。
+async fn install_update_inner(app: &AppHandle) -> Result<(), String> {
+    let pending = app
+        .try_state::<UpdaterState>()
+        .and_then(|state| state.pending.lock().ok().and_then(|mut guard| guard.take()))
+        .ok_or_else(|| "No update available to install".to_string())?;
+
+    if let Err(e) = pending
+        .download_and_install(|_chunk_length, _content_length| {}, || {})
+        .await
+    {
+        // 安裝失敗時把待處理的更新放回去，讓使用者可以重試，而不必等下一次自動檢查。
+        if let Some(state) = app.try_state::<UpdaterState>() {
+            if let Ok(mut guard) = state.pending.lock() {
+                *guard = Some(pending);
+            }
+        }
+        return Err(e.to_string());
+    }
+
+    info!("更新已下載完成，準備重新啟動");
+    cleanup_sidecar(app);
+    app.restart();
+}
+
+/// 檢查是否有新版本，供前端主動觸發（啟動時與定時任務也會呼叫同一邏輯）。
+#[tauri::command]
+async fn check_for_updates(app: AppHandle) -> Result<Option<UpdateInfo>, String> {
+    check_for_updates_inner(&app).await
+}
+
+/// 安裝目前待處理的更新並重新啟動應用程式。
+#[tauri::command]
+async fn install_update(app: AppHandle) -> Result<(), String> {
+    install_update_inner(&app).await
+}
+
+/// 在真正退出前（未啟用縮到系統匣時關閉視窗、系統匣 Quit、安裝更新後重新啟動前）清理 sidecar
+/// 程序，並通知監控任務停止以免重新產生子程序。
 fn cleanup_sidecar(app: &AppHandle) {
     if let Some(state) = app.try_state::<SidecarState>() {
+        state.shutdown.store(true, Ordering::Relaxed);
         if let Ok(mut child_guard) = state.child.lock() {
             if let Some(child) = child_guard.take() {
                 if let Err(e) = child.kill() {
@@ -173,10 +979,16 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_process::init())
+        .register_uri_scheme_protocol("starscope", |ctx, request| {
+            handle_data_protocol(ctx.app_handle(), &request)
+        })
         .setup(|app| {
             #[cfg(target_os = "macos")]
             if let Some(window) = app.get_webview_window("main") {
                 disable_fullscreen_button(&window);
+                setup_custom_titlebar(&window);
             }
 
             if let Ok(app_data_dir) = app.path().app_data_dir() {
@@ -186,17 +998,71 @@ pub fn run() {
                 );
             }
 
+            let preferences = load_preferences(app.handle());
+            app.manage(PreferencesState {
+                close_to_tray: Mutex::new(preferences.close_to_tray),
+            });
+            app.manage(WindowSaveState {
+                generation: Arc::new(AtomicU64::new(0)),
+            });
+
+            if let Some(window) = app.get_webview_window("main") {
+                restore_window_geometry(&window);
+            }
+
             start_sidecar(app);
             setup_tray(app)?;
+            app.manage(UpdaterState {
+                pending: Mutex::new(None),
+            });
+
+            let updater_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    if let Err(e) = check_for_updates_inner(&updater_handle).await {
+                        warn!("Update check failed: {e}");
+                    }
+                    tokio::time::sleep(UPDATE_CHECK_INTERVAL).await;
+                }
+            });
 
             Ok(())
         })
         .on_window_event(|window, event| {
-            if let WindowEvent::CloseRequested { .. } = event {
-                cleanup_sidecar(window.app_handle());
+            if window.label() != "main" {
+                return;
+            }
+            match event {
+                WindowEvent::Moved(_) | WindowEvent::Resized(_) => {
+                    schedule_save_window_geometry(window.app_handle());
+                }
+                WindowEvent::CloseRequested { api, .. } => {
+                    if let Some(main) = window.app_handle().get_webview_window("main") {
+                        save_window_geometry(&main);
+                    }
+                    if is_close_to_tray_enabled(window.app_handle()) {
+                        api.prevent_close();
+                        if let Some(main) = window.app_handle().get_webview_window("main") {
+                            if let Err(e) = main.hide() {
+                                warn!("Failed to hide main window: {e}");
+                            }
+                        }
+                    } else {
+                        cleanup_sidecar(window.app_handle());
+                    }
+                }
+                _ => {}
             }
         })
-        .invoke_handler(tauri::generate_handler![greet])
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            sidecar_status,
+            set_titlebar_buttons_inset,
+            update_tray,
+            check_for_updates,
+            install_update,
+            set_close_to_tray
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }